@@ -0,0 +1,188 @@
+//! Set-difference ("exclude") support for [`Ipv4Net`], [`Ipv6Net`], and
+//! [`IpNet`].
+//!
+//! These methods complement [`subnets()`] and [`aggregate()`] by
+//! computing the smallest set of aligned prefixes that cover a network
+//! minus one or more excluded prefixes, which is the standard operation
+//! needed to build firewall rule sets and address allocators.
+//!
+//! [`subnets()`]: ../enum.IpNet.html#method.subnets
+//! [`aggregate()`]: ../enum.IpNet.html#method.aggregate
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{IpNet, Ipv4Net, Ipv6Net};
+
+/// Splits a network into its two half-size subnets.
+trait Halves: Copy {
+    fn halves(&self) -> (Self, Self);
+}
+
+macro_rules! impl_exclude {
+    ($net:ident, $exclude_iter:ident) => {
+        impl Halves for $net {
+            fn halves(&self) -> (Self, Self) {
+                let new_prefix_len = self.prefix_len() + 1;
+                let mut subnets = self.subnets(new_prefix_len).expect(
+                    "prefix_len() < max_prefix_len() is guaranteed by the caller before splitting",
+                );
+                let lower = subnets.next().unwrap();
+                let upper = subnets.next().unwrap();
+                (lower, upper)
+            }
+        }
+
+        impl $net {
+            /// Returns an iterator yielding the smallest set of aligned
+            /// prefixes that cover `self` but not `other`.
+            ///
+            /// If `other` does not overlap `self`, the iterator yields
+            /// `self` unchanged. If `other` fully covers `self`, the
+            /// iterator yields nothing.
+            pub fn exclude(&self, other: &$net) -> $exclude_iter {
+                $exclude_iter {
+                    remaining: Some(*self),
+                    target: *other,
+                }
+            }
+        }
+
+        /// An iterator over the prefixes covering a network minus an
+        /// excluded prefix.
+        ///
+        /// Created by the [`exclude()`] method.
+        ///
+        #[doc = concat!("[`exclude()`]: struct.", stringify!($net), ".html#method.exclude")]
+        pub struct $exclude_iter {
+            remaining: Option<$net>,
+            target: $net,
+        }
+
+        impl Iterator for $exclude_iter {
+            type Item = $net;
+
+            fn next(&mut self) -> Option<$net> {
+                let block = self.remaining.take()?;
+                if self.target.contains(&block) {
+                    // `target` covers `block` entirely; nothing left to yield.
+                    return None;
+                }
+                if !block.contains(&self.target) {
+                    // `target` doesn't overlap `block` (the common case being
+                    // the very first call, when `target` isn't inside `self`
+                    // at all); nothing to split, yield it unchanged.
+                    return Some(block);
+                }
+                // `block` strictly contains `target` and `target` doesn't cover
+                // `block` (ruled out above), so `block` has room to split further.
+                let (lower, upper) = block.halves();
+                let (keep, recurse) = if lower.contains(&self.target) {
+                    (upper, lower)
+                } else {
+                    (lower, upper)
+                };
+                self.remaining = Some(recurse);
+                Some(keep)
+            }
+        }
+    };
+}
+
+impl_exclude!(Ipv4Net, Ipv4Exclude);
+impl_exclude!(Ipv6Net, Ipv6Exclude);
+
+impl IpNet {
+    /// Returns the smallest set of aligned prefixes that cover `self`
+    /// but none of `others`.
+    ///
+    /// Each of `others` is subtracted from `self` in turn; prefixes of
+    /// a different address family are ignored.
+    pub fn exclude_all(&self, others: &[IpNet]) -> Vec<IpNet> {
+        let mut remaining = vec![*self];
+        for other in others {
+            let mut next = Vec::new();
+            for net in remaining {
+                match (net, other) {
+                    (IpNet::V4(net), IpNet::V4(other)) => {
+                        next.extend(net.exclude(other).map(IpNet::V4));
+                    }
+                    (IpNet::V6(net), IpNet::V6(other)) => {
+                        next.extend(net.exclude(other).map(IpNet::V6));
+                    }
+                    _ => next.push(net),
+                }
+            }
+            remaining = next;
+        }
+        remaining
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::net::{Ipv4Addr, Ipv6Addr};
+
+    fn v4(addr: [u8; 4], prefix_len: u8) -> Ipv4Net {
+        Ipv4Net::new(Ipv4Addr::from(addr), prefix_len).unwrap()
+    }
+
+    #[test]
+    fn disjoint_yields_self_unchanged() {
+        let base = v4([10, 0, 0, 0], 24);
+        let other = v4([192, 168, 0, 0], 24);
+        let pieces: Vec<_> = base.exclude(&other).collect();
+        assert_eq!(pieces, vec![base]);
+    }
+
+    #[test]
+    fn equal_yields_nothing() {
+        let base = v4([10, 0, 0, 0], 24);
+        let pieces: Vec<_> = base.exclude(&base).collect();
+        assert_eq!(pieces, vec![]);
+    }
+
+    #[test]
+    fn contained_excludes_the_hole() {
+        let base = v4([10, 0, 0, 0], 24);
+        let hole = v4([10, 0, 0, 64], 26);
+        let mut pieces: Vec<_> = base.exclude(&hole).collect();
+        pieces.sort();
+        let mut expected = vec![v4([10, 0, 0, 0], 26), v4([10, 0, 0, 128], 25)];
+        expected.sort();
+        assert_eq!(pieces, expected);
+    }
+
+    #[test]
+    fn target_covers_block() {
+        let block = v4([10, 0, 0, 0], 24);
+        let cover = v4([10, 0, 0, 0], 8);
+        let pieces: Vec<_> = block.exclude(&cover).collect();
+        assert_eq!(pieces, vec![]);
+    }
+
+    #[test]
+    fn exclude_all_ignores_mismatched_family() {
+        let base = IpNet::V4(v4([10, 0, 0, 0], 24));
+        let other = IpNet::V6(Ipv6Net::new(Ipv6Addr::UNSPECIFIED, 64).unwrap());
+        assert_eq!(base.exclude_all(&[other]), vec![base]);
+    }
+
+    #[test]
+    fn exclude_all_subtracts_multiple_holes() {
+        let base = IpNet::V4(v4([10, 0, 0, 0], 24));
+        let holes = vec![
+            IpNet::V4(v4([10, 0, 0, 0], 26)),
+            IpNet::V4(v4([10, 0, 0, 128], 26)),
+        ];
+        let mut remaining = base.exclude_all(&holes);
+        remaining.sort();
+        let mut expected = vec![
+            IpNet::V4(v4([10, 0, 0, 64], 26)),
+            IpNet::V4(v4([10, 0, 0, 192], 26)),
+        ];
+        expected.sort();
+        assert_eq!(remaining, expected);
+    }
+}
@@ -5,6 +5,50 @@ use crate::Ipv6Net;
 use schemars::{json_schema, schema_for, JsonSchema, Schema, SchemaGenerator};
 use std::borrow::Cow;
 
+/// Matches the dotted-decimal octets accepted by [`parser`], i.e. each
+/// octet is `0`-`255` with no leading zeros.
+///
+/// [`parser`]: ../parser/index.html
+const IPV4_OCTET: &str = r"(?:25[0-5]|2[0-4][0-9]|1[0-9][0-9]|[1-9][0-9]|[0-9])";
+
+/// Matches a dotted-decimal IPv4 address, e.g. embedded at the tail of
+/// an IPv6 address.
+fn ipv4_address() -> alloc::string::String {
+    alloc::format!("(?:{octet}\\.){{3}}{octet}", octet = IPV4_OCTET)
+}
+
+/// Matches an IPv4 prefix length, `0`-`32`, with no leading zeros.
+const IPV4_PREFIX_LEN: &str = r"(?:3[0-2]|[1-2][0-9]|[0-9])";
+
+/// Matches an IPv6 prefix length, `0`-`128`, with no leading zeros.
+const IPV6_PREFIX_LEN: &str = r"(?:12[0-8]|1[0-1][0-9]|[1-9][0-9]|[0-9])";
+
+/// Matches the eight-group and `::`-compressed forms of an IPv6
+/// address, and an embedded-IPv4 suffix when it follows `::`,
+/// `::ffff:`, `::ffff:0:`, or one to four groups and `::` (e.g.
+/// `::ffff:192.168.0.1`). Other embedded-IPv4 forms, such as six
+/// groups followed by an IPv4 address, are not matched.
+fn ipv6_address() -> alloc::string::String {
+    alloc::format!(
+        concat!(
+            r"(?:",
+            r"(?:[0-9A-Fa-f]{{1,4}}:){{7}}[0-9A-Fa-f]{{1,4}}",
+            r"|(?:[0-9A-Fa-f]{{1,4}}:){{1,7}}:",
+            r"|(?:[0-9A-Fa-f]{{1,4}}:){{1,6}}:[0-9A-Fa-f]{{1,4}}",
+            r"|(?:[0-9A-Fa-f]{{1,4}}:){{1,5}}(?::[0-9A-Fa-f]{{1,4}}){{1,2}}",
+            r"|(?:[0-9A-Fa-f]{{1,4}}:){{1,4}}(?::[0-9A-Fa-f]{{1,4}}){{1,3}}",
+            r"|(?:[0-9A-Fa-f]{{1,4}}:){{1,3}}(?::[0-9A-Fa-f]{{1,4}}){{1,4}}",
+            r"|(?:[0-9A-Fa-f]{{1,4}}:){{1,2}}(?::[0-9A-Fa-f]{{1,4}}){{1,5}}",
+            r"|[0-9A-Fa-f]{{1,4}}:(?:(?::[0-9A-Fa-f]{{1,4}}){{1,6}})",
+            r"|:(?:(?::[0-9A-Fa-f]{{1,4}}){{1,7}}|:)",
+            r"|(?:[0-9A-Fa-f]{{1,4}}:){{1,4}}:{ipv4}",
+            r"|::(?:ffff(?::0{{1,4}})?:)?{ipv4}",
+            r")"
+        ),
+        ipv4 = ipv4_address(),
+    )
+}
+
 impl JsonSchema for Ipv4Net {
     fn schema_name() -> Cow<'static, str> {
         "Ipv4Net".into()
@@ -13,14 +57,15 @@ impl JsonSchema for Ipv4Net {
     fn json_schema(_: &mut SchemaGenerator) -> Schema {
         json_schema!({
             "title": "IPv4 network",
-            "description": "An IPv4 address with prefix length",
+            "description": "An IPv4 address with prefix length, in canonical or host-bits-set form",
             "examples": [
                 "0.0.0.0/0",
                 "192.168.0.0/24"
             ],
             "type": "string",
+            "format": "ipv4-network",
             "maxLength": 18,
-            "pattern": r#"^(?:(?:25[0-5]|2[0-4][0-9]|1[0-9][0-9]|[1-9][0-9]|[0-9])\.){3}(?:25[0-5]|2[0-4][0-9]|1[0-9][0-9]|[1-9][0-9]|[0-9])\/(?:3[0-2]|[1-2][0-9]|[0-9])$"#
+            "pattern": alloc::format!("^{}/{}$", ipv4_address(), IPV4_PREFIX_LEN)
         })
     }
 }
@@ -32,14 +77,15 @@ impl JsonSchema for Ipv6Net {
     fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
         json_schema!({
             "title": "IPv6 network",
-            "description": "An IPv6 address with prefix length",
+            "description": "An IPv6 address with prefix length, in canonical or host-bits-set form",
             "examples": [
                 "::/0",
                 "fd00::/32"
             ],
             "type": "string",
             "maxLength": 43,
-            "pattern": r#"^[0-9A-Fa-f:\.]+\/(?:[0-9]|[1-9][0-9]|1[0-1][0-9]|12[0-8])$"#
+            "format": "ipv6-network",
+            "pattern": alloc::format!("^{}/{}$", ipv6_address(), IPV6_PREFIX_LEN)
         })
     }
 }
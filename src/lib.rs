@@ -36,6 +36,13 @@
 //! * The [`IpAdd`], [`IpSub`], [`IpBitAnd`], [`IpBitOr`] traits extend
 //!   the [`Ipv4Addr`] and [`Ipv6Addr`] types with methods to perform
 //!   these operations.
+//! * [`IpNetTrie`], [`Ipv4NetTrie`], and [`Ipv6NetTrie`] are
+//!   longest-prefix-match lookup tables that associate values with
+//!   stored prefixes and can be queried by address.
+//! * [`Ipv4Exclude`] and [`Ipv6Exclude`] are iterators that generate
+//!   the smallest set of aligned prefixes covering a network minus an
+//!   excluded prefix. These are returned by the [`exclude()`] methods
+//!   and used within the [`exclude_all()`] method.
 //!
 //! [`IpNet`]: enum.IpNet.html
 //! [`Ipv4Net`]: struct.Ipv4Net.html
@@ -56,6 +63,13 @@
 //! [`IpSub`]: trait.IpSub.html
 //! [`IpBitAnd`]: trait.IpBitAnd.html
 //! [`IpBitOr`]: trait.IpBitOr.html
+//! [`IpNetTrie`]: struct.IpNetTrie.html
+//! [`Ipv4NetTrie`]: struct.Ipv4NetTrie.html
+//! [`Ipv6NetTrie`]: struct.Ipv6NetTrie.html
+//! [`Ipv4Exclude`]: struct.Ipv4Exclude.html
+//! [`Ipv6Exclude`]: struct.Ipv6Exclude.html
+//! [`exclude()`]: struct.Ipv4Net.html#method.exclude
+//! [`exclude_all()`]: enum.IpNet.html#method.exclude_all
 //!
 //! # Features
 //!
@@ -88,7 +102,15 @@
 //! length. The `IpNet` type will serialize to an Enum with the V4 or V6
 //! variant index prepending the above string of 5 or 17 bytes.
 //!
+//! The default impls round-trip whatever the input string contains,
+//! including non-canonical prefixes with host bits set. The
+//! [`serde_strict`] and [`serde_trunc`] adaptor modules can be
+//! selected per field with `#[serde(with = "...")]` to instead reject
+//! or truncate host bits on deserialization.
+//!
 //! [`serde`]: https://serde.rs
+//! [`serde_strict`]: serde_strict/index.html
+//! [`serde_trunc`]: serde_trunc/index.html
 //!
 //! ## "heapless" [^1]
 //!
@@ -138,21 +160,29 @@ extern crate schemars1;
 #[cfg(feature = "serde")]
 extern crate serde;
 
+pub use self::exclude::{Ipv4Exclude, Ipv6Exclude};
 pub use self::ipext::{IpAdd, IpAddrRange, IpBitAnd, IpBitOr, IpSub, Ipv4AddrRange, Ipv6AddrRange};
 pub use self::ipnet::{
     IpNet, IpSubnets, Ipv4Net, Ipv4Subnets, Ipv6Net, Ipv6Subnets, PrefixLenError,
 };
 pub use self::mask::{ip_mask_to_prefix, ipv4_mask_to_prefix, ipv6_mask_to_prefix};
 pub use self::parser::AddrParseError;
+#[cfg(feature = "serde")]
+pub use self::serde_adaptors::{serde_strict, serde_trunc};
+pub use self::trie::{IpNetTrie, Ipv4NetTrie, Ipv6NetTrie};
 
+mod exclude;
 mod ipext;
 mod ipnet;
 mod mask;
 mod parser;
+mod trie;
 
 #[cfg(feature = "schemars08")]
 mod ipnet_schemars_08;
 #[cfg(feature = "schemars1")]
 mod ipnet_schemars_1;
 #[cfg(feature = "serde")]
+mod serde_adaptors;
+#[cfg(feature = "serde")]
 mod ipnet_serde;
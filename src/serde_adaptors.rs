@@ -0,0 +1,127 @@
+//! Serde adaptors enforcing a particular host-bits policy on ingest.
+//!
+//! The default `Serialize`/`Deserialize` impls (see the `serde`
+//! feature) round-trip whatever the input string contains, including
+//! non-canonical prefixes like `192.168.1.5/24` where bits outside the
+//! prefix are set. These adaptors are meant to be selected per field
+//! with `#[serde(with = "...")]` when that default is a footgun:
+//!
+//! * [`serde_strict`] rejects non-canonical prefixes outright.
+//! * [`serde_trunc`] silently truncates them to their network address.
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct Config {
+//!     #[serde(with = "ipnet::serde_strict")]
+//!     subnet: Ipv4Net,
+//! }
+//! ```
+
+use alloc::format;
+use core::fmt;
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+
+use crate::{IpNet, Ipv4Net, Ipv6Net};
+
+/// Implemented by [`Ipv4Net`], [`Ipv6Net`], and [`IpNet`] so the
+/// adaptors in this module can check and enforce host-bits policy
+/// independently of address family.
+pub trait HostBits: Sized + PartialEq + Copy {
+    /// Returns `true` if any bits outside the prefix are set.
+    fn has_host_bits_set(&self) -> bool;
+
+    /// Returns the network address, with host bits cleared.
+    fn trunc(&self) -> Self;
+}
+
+impl HostBits for Ipv4Net {
+    fn has_host_bits_set(&self) -> bool {
+        self.trunc() != *self
+    }
+    fn trunc(&self) -> Self {
+        Ipv4Net::trunc(self)
+    }
+}
+
+impl HostBits for Ipv6Net {
+    fn has_host_bits_set(&self) -> bool {
+        self.trunc() != *self
+    }
+    fn trunc(&self) -> Self {
+        Ipv6Net::trunc(self)
+    }
+}
+
+impl HostBits for IpNet {
+    fn has_host_bits_set(&self) -> bool {
+        self.trunc() != *self
+    }
+    fn trunc(&self) -> Self {
+        IpNet::trunc(self)
+    }
+}
+
+/// Serializes via the type's own [`Serialize`] impl, identically to the
+/// default impl, so the human-readable string form and the compact
+/// binary form are both preserved depending on the target format.
+fn serialize<S, T>(net: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    net.serialize(serializer)
+}
+
+/// Deserializes via the type's own [`Deserialize`] impl, rejecting
+/// prefixes with host bits set.
+pub mod serde_strict {
+    use super::*;
+
+    pub fn serialize<S, T>(net: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        super::serialize(net, serializer)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + HostBits + fmt::Display,
+    {
+        let net = T::deserialize(deserializer)?;
+        if net.has_host_bits_set() {
+            return Err(de::Error::custom(format!(
+                "{} has host bits set; expected a canonical network address",
+                net
+            )));
+        }
+        Ok(net)
+    }
+}
+
+/// Deserializes via the type's own [`Deserialize`] impl, truncating
+/// host bits to zero instead of rejecting them.
+pub mod serde_trunc {
+    use super::*;
+
+    pub fn serialize<S, T>(net: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        super::serialize(net, serializer)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + HostBits,
+    {
+        let net = T::deserialize(deserializer)?;
+        Ok(net.trunc())
+    }
+}
@@ -0,0 +1,519 @@
+//! A binary (Patricia/radix) trie keyed on IP prefixes, supporting
+//! longest-prefix-match lookups.
+//!
+//! This module provides [`Ipv4NetTrie`], [`Ipv6NetTrie`], and the
+//! [`IpNetTrie`] wrapper that dispatches between the two depending on
+//! the address family being inserted or queried. These are the natural
+//! data structure for routing tables, ACLs, and geo/ASN lookups, where
+//! values need to be associated with prefixes and later retrieved by
+//! the most specific prefix covering a queried address.
+//!
+//! [`Ipv4NetTrie`]: struct.Ipv4NetTrie.html
+//! [`Ipv6NetTrie`]: struct.Ipv6NetTrie.html
+//! [`IpNetTrie`]: struct.IpNetTrie.html
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::{IpNet, Ipv4Net, Ipv6Net};
+
+/// A trait implemented by `Ipv4Addr` and `Ipv6Addr` that exposes the
+/// address as a fixed-width unsigned integer so the trie can walk its
+/// bits independently of address family.
+trait TrieAddr: Copy + PartialEq {
+    const WIDTH: u8;
+    fn bits(self) -> u128;
+    fn bit(self, index: u8) -> bool {
+        (self.bits() >> (Self::WIDTH - 1 - index)) & 1 == 1
+    }
+}
+
+impl TrieAddr for Ipv4Addr {
+    const WIDTH: u8 = 32;
+    fn bits(self) -> u128 {
+        u32::from(self) as u128
+    }
+}
+
+impl TrieAddr for Ipv6Addr {
+    const WIDTH: u8 = 128;
+    fn bits(self) -> u128 {
+        u128::from(self)
+    }
+}
+
+/// A single node in a [`Ipv4NetTrie`] or [`Ipv6NetTrie`].
+///
+/// Chains of nodes with a single child are compressed: `skip` records
+/// how many additional bits, beyond the parent's prefix length, are
+/// shared by every descendant before the next branch, and `key` stores
+/// those bits so they can all be checked at this one node instead of
+/// at one node per bit.
+struct Node<A, T> {
+    key: A,
+    prefix_len: u8,
+    skip: u8,
+    value: Option<T>,
+    children: [Option<Box<Node<A, T>>>; 2],
+}
+
+impl<A: TrieAddr, T> Node<A, T> {
+    fn leaf(key: A, prefix_len: u8, skip: u8, value: Option<T>) -> Self {
+        Node {
+            key,
+            prefix_len,
+            skip,
+            value,
+            children: [None, None],
+        }
+    }
+}
+
+/// A Patricia trie mapping IPv4 network prefixes to values of type `T`.
+///
+/// Supports insertion and removal of exact prefixes, exact-prefix
+/// lookup, and longest-prefix-match queries against an address. See
+/// the [module documentation][self] for an overview.
+pub struct Ipv4NetTrie<T> {
+    root: Option<Box<Node<Ipv4Addr, T>>>,
+    len: usize,
+}
+
+/// A Patricia trie mapping IPv6 network prefixes to values of type `T`.
+///
+/// Supports insertion and removal of exact prefixes, exact-prefix
+/// lookup, and longest-prefix-match queries against an address. See
+/// the [module documentation][self] for an overview.
+pub struct Ipv6NetTrie<T> {
+    root: Option<Box<Node<Ipv6Addr, T>>>,
+    len: usize,
+}
+
+macro_rules! impl_net_trie {
+    ($trie:ident, $addr:ty, $net:ty, $new_net:expr) => {
+        impl<T> $trie<T> {
+            /// Creates an empty trie.
+            pub fn new() -> Self {
+                $trie { root: None, len: 0 }
+            }
+
+            /// Returns the number of prefixes stored in the trie.
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            /// Returns `true` if the trie holds no prefixes.
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            /// Inserts `value` for `net`, returning the previous value
+            /// stored for that exact prefix, if any.
+            pub fn insert(&mut self, net: $net, value: T) -> Option<T> {
+                let key = net.network();
+                let prefix_len = net.prefix_len();
+                let (old, grew) = Self::insert_node(&mut self.root, key, prefix_len, 0, value);
+                if grew {
+                    self.len += 1;
+                }
+                old
+            }
+
+            fn insert_node(
+                slot: &mut Option<Box<Node<$addr, T>>>,
+                key: $addr,
+                prefix_len: u8,
+                depth: u8,
+                value: T,
+            ) -> (Option<T>, bool) {
+                match slot {
+                    None => {
+                        *slot = Some(Box::new(Node::leaf(
+                            key,
+                            prefix_len,
+                            prefix_len - depth,
+                            Some(value),
+                        )));
+                        (None, true)
+                    }
+                    Some(node) => {
+                        let remaining = prefix_len - depth;
+                        let max_common = node.skip.min(remaining);
+                        let common = common_prefix_len(node.key, key, depth, max_common);
+                        if common == node.skip && common == remaining {
+                            // `key` matches this node's prefix exactly. A branch
+                            // node may still hold the key of the insert that
+                            // created it, so store this prefix's own key.
+                            node.key = key;
+                            let old = node.value.replace(value);
+                            let grew = old.is_none();
+                            (old, grew)
+                        } else if common == node.skip {
+                            // This node's prefix is a strict prefix of `key`;
+                            // descend into the child selected by the next bit.
+                            let child_depth = depth + node.skip;
+                            let idx = key.bit(child_depth) as usize;
+                            Self::insert_node(
+                                &mut node.children[idx],
+                                key,
+                                prefix_len,
+                                child_depth,
+                                value,
+                            )
+                        } else {
+                            // Split: insert a new branch node above both the
+                            // existing node and the new prefix at the point
+                            // where their bits diverge.
+                            let branch_depth = depth + common;
+                            let mut old_node = Box::new(Node::leaf(
+                                node.key,
+                                node.prefix_len,
+                                node.prefix_len - branch_depth,
+                                None,
+                            ));
+                            old_node.children = core::mem::replace(
+                                &mut node.children,
+                                [None, None],
+                            );
+                            old_node.value = node.value.take();
+
+                            let new_at_branch = branch_depth == prefix_len;
+                            let mut branch = Box::new(Node::leaf(key, branch_depth, common, None));
+                            let old_idx = node.key.bit(branch_depth) as usize;
+                            branch.children[old_idx] = Some(old_node);
+                            let grew = if new_at_branch {
+                                branch.value = Some(value);
+                                true
+                            } else {
+                                let new_idx = key.bit(branch_depth) as usize;
+                                let (_, grew) = Self::insert_node(
+                                    &mut branch.children[new_idx],
+                                    key,
+                                    prefix_len,
+                                    branch_depth,
+                                    value,
+                                );
+                                grew
+                            };
+                            **node = *branch;
+                            (None, grew)
+                        }
+                    }
+                }
+            }
+
+            /// Removes the value stored for the exact prefix `net`,
+            /// returning it if it was present. Child prefixes, if any,
+            /// are retained.
+            pub fn remove(&mut self, net: $net) -> Option<T> {
+                let key = net.network();
+                let prefix_len = net.prefix_len();
+                let removed = Self::remove_node(&mut self.root, key, prefix_len, 0);
+                if removed.is_some() {
+                    self.len -= 1;
+                }
+                removed
+            }
+
+            fn remove_node(
+                slot: &mut Option<Box<Node<$addr, T>>>,
+                key: $addr,
+                prefix_len: u8,
+                depth: u8,
+            ) -> Option<T> {
+                let node = slot.as_mut()?;
+                let common = common_prefix_len(node.key, key, depth, node.skip);
+                if common < node.skip {
+                    return None;
+                }
+                if node.prefix_len == prefix_len {
+                    return node.value.take();
+                }
+                let child_depth = depth + node.skip;
+                if child_depth >= <$addr as TrieAddr>::WIDTH {
+                    return None;
+                }
+                let idx = key.bit(child_depth) as usize;
+                Self::remove_node(&mut node.children[idx], key, prefix_len, child_depth)
+            }
+
+            /// Returns a reference to the value stored for the exact
+            /// prefix `net`, if any.
+            pub fn get_exact(&self, net: $net) -> Option<&T> {
+                let key = net.network();
+                let prefix_len = net.prefix_len();
+                let mut cur = self.root.as_deref();
+                let mut depth = 0;
+                while let Some(node) = cur {
+                    let common = common_prefix_len(node.key, key, depth, node.skip);
+                    if common < node.skip {
+                        return None;
+                    }
+                    if node.prefix_len == prefix_len {
+                        return node.value.as_ref();
+                    }
+                    depth += node.skip;
+                    if depth >= <$addr as TrieAddr>::WIDTH {
+                        return None;
+                    }
+                    cur = node.children[key.bit(depth) as usize].as_deref();
+                }
+                None
+            }
+
+            /// Returns the most specific stored prefix that contains
+            /// `addr`, along with a reference to its value.
+            pub fn longest_match(&self, addr: $addr) -> Option<($net, &T)> {
+                let mut cur = self.root.as_deref();
+                let mut depth = 0;
+                let mut best: Option<&Node<$addr, T>> = None;
+                while let Some(node) = cur {
+                    let common = common_prefix_len(node.key, addr, depth, node.skip);
+                    if common < node.skip {
+                        break;
+                    }
+                    if node.value.is_some() {
+                        best = Some(node);
+                    }
+                    depth += node.skip;
+                    if depth >= <$addr as TrieAddr>::WIDTH {
+                        break;
+                    }
+                    cur = node.children[addr.bit(depth) as usize].as_deref();
+                }
+                best.map(|node| {
+                    let net = $new_net(node.key, node.prefix_len);
+                    (net, node.value.as_ref().unwrap())
+                })
+            }
+
+            /// Returns an iterator over every stored prefix that
+            /// contains `addr`, ordered from least to most specific.
+            pub fn matches(&self, addr: $addr) -> alloc::vec::IntoIter<($net, &T)> {
+                let mut matches = Vec::new();
+                let mut cur = self.root.as_deref();
+                let mut depth = 0;
+                while let Some(node) = cur {
+                    let common = common_prefix_len(node.key, addr, depth, node.skip);
+                    if common < node.skip {
+                        break;
+                    }
+                    if let Some(value) = node.value.as_ref() {
+                        matches.push(($new_net(node.key, node.prefix_len), value));
+                    }
+                    depth += node.skip;
+                    if depth >= <$addr as TrieAddr>::WIDTH {
+                        break;
+                    }
+                    cur = node.children[addr.bit(depth) as usize].as_deref();
+                }
+                matches.into_iter()
+            }
+        }
+
+        impl<T> Default for $trie<T> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+impl_net_trie!(Ipv4NetTrie, Ipv4Addr, Ipv4Net, |addr, len| Ipv4Net::new(addr, len)
+    .expect("prefix length recorded in the trie is always valid"));
+impl_net_trie!(Ipv6NetTrie, Ipv6Addr, Ipv6Net, |addr, len| Ipv6Net::new(addr, len)
+    .expect("prefix length recorded in the trie is always valid"));
+
+/// Number of leading bits, starting at `depth`, that `node_key` and
+/// `key` have in common, capped at `skip` bits past `depth`.
+fn common_prefix_len<A: TrieAddr>(node_key: A, key: A, depth: u8, skip: u8) -> u8 {
+    let mut i = 0;
+    while i < skip && node_key.bit(depth + i) == key.bit(depth + i) {
+        i += 1;
+    }
+    i
+}
+
+/// A trie mapping IP network prefixes of either address family to
+/// values of type `T`, backed by an [`Ipv4NetTrie`] and an
+/// [`Ipv6NetTrie`].
+pub struct IpNetTrie<T> {
+    v4: Ipv4NetTrie<T>,
+    v6: Ipv6NetTrie<T>,
+}
+
+impl<T> Default for IpNetTrie<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> IpNetTrie<T> {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        IpNetTrie {
+            v4: Ipv4NetTrie::new(),
+            v6: Ipv6NetTrie::new(),
+        }
+    }
+
+    /// Returns the number of prefixes stored in the trie, across both
+    /// address families.
+    pub fn len(&self) -> usize {
+        self.v4.len() + self.v6.len()
+    }
+
+    /// Returns `true` if the trie holds no prefixes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts `value` for `net`, returning the previous value stored
+    /// for that exact prefix, if any.
+    pub fn insert(&mut self, net: IpNet, value: T) -> Option<T> {
+        match net {
+            IpNet::V4(net) => self.v4.insert(net, value),
+            IpNet::V6(net) => self.v6.insert(net, value),
+        }
+    }
+
+    /// Removes the value stored for the exact prefix `net`, returning
+    /// it if it was present.
+    pub fn remove(&mut self, net: IpNet) -> Option<T> {
+        match net {
+            IpNet::V4(net) => self.v4.remove(net),
+            IpNet::V6(net) => self.v6.remove(net),
+        }
+    }
+
+    /// Returns a reference to the value stored for the exact prefix
+    /// `net`, if any.
+    pub fn get_exact(&self, net: IpNet) -> Option<&T> {
+        match net {
+            IpNet::V4(net) => self.v4.get_exact(net),
+            IpNet::V6(net) => self.v6.get_exact(net),
+        }
+    }
+
+    /// Returns the most specific stored prefix that contains `addr`,
+    /// along with a reference to its value.
+    pub fn longest_match(&self, addr: IpAddr) -> Option<(IpNet, &T)> {
+        match addr {
+            IpAddr::V4(addr) => self
+                .v4
+                .longest_match(addr)
+                .map(|(net, value)| (IpNet::V4(net), value)),
+            IpAddr::V6(addr) => self
+                .v6
+                .longest_match(addr)
+                .map(|(net, value)| (IpNet::V6(net), value)),
+        }
+    }
+
+    /// Returns an iterator over every stored prefix that contains
+    /// `addr`, ordered from least to most specific.
+    pub fn matches(&self, addr: IpAddr) -> alloc::vec::IntoIter<(IpNet, &T)> {
+        let matches: Vec<_> = match addr {
+            IpAddr::V4(addr) => self
+                .v4
+                .matches(addr)
+                .map(|(net, value)| (IpNet::V4(net), value))
+                .collect(),
+            IpAddr::V6(addr) => self
+                .v6
+                .matches(addr)
+                .map(|(net, value)| (IpNet::V6(net), value))
+                .collect(),
+        };
+        matches.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_get_exact_remove() {
+        let mut t: Ipv4NetTrie<u32> = Ipv4NetTrie::new();
+        let net = Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 0), 24).unwrap();
+        assert_eq!(t.insert(net, 1), None);
+        assert_eq!(t.len(), 1);
+        assert_eq!(t.insert(net, 2), Some(1));
+        assert_eq!(t.len(), 1);
+        assert_eq!(t.get_exact(net), Some(&2));
+        assert_eq!(t.remove(net), Some(2));
+        assert_eq!(t.get_exact(net), None);
+        assert_eq!(t.len(), 0);
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn host_prefix_boundaries_v4() {
+        let mut t: Ipv4NetTrie<&str> = Ipv4NetTrie::new();
+        let host = Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 1), 32).unwrap();
+        t.insert(host, "host");
+        assert_eq!(
+            t.longest_match(Ipv4Addr::new(10, 0, 0, 1)),
+            Some((host, &"host"))
+        );
+        assert_eq!(t.matches(Ipv4Addr::new(10, 0, 0, 1)).count(), 1);
+
+        // Removing a shorter prefix that covers the /32 but was never
+        // inserted must not panic and must leave the /32 untouched.
+        let default_route = Ipv4Net::new(Ipv4Addr::new(0, 0, 0, 0), 0).unwrap();
+        assert_eq!(t.remove(default_route), None);
+        assert_eq!(t.get_exact(host), Some(&"host"));
+    }
+
+    #[test]
+    fn host_prefix_boundaries_v6() {
+        let mut t: Ipv6NetTrie<&str> = Ipv6NetTrie::new();
+        let addr: Ipv6Addr = "fd00::1".parse().unwrap();
+        let host = Ipv6Net::new(addr, 128).unwrap();
+        t.insert(host, "host");
+        assert_eq!(t.longest_match(addr), Some((host, &"host")));
+        assert_eq!(t.matches(addr).count(), 1);
+
+        let default_route = Ipv6Net::new(Ipv6Addr::UNSPECIFIED, 0).unwrap();
+        assert_eq!(t.remove(default_route), None);
+        assert_eq!(t.get_exact(host), Some(&"host"));
+    }
+
+    #[test]
+    fn matches_orders_least_to_most_specific() {
+        let mut t: Ipv4NetTrie<&str> = Ipv4NetTrie::new();
+        let net8 = Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 0), 8).unwrap();
+        let net24 = Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 0), 24).unwrap();
+        let net32 = Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 1), 32).unwrap();
+        t.insert(net24, "b");
+        t.insert(net8, "a");
+        t.insert(net32, "c");
+        let matches: Vec<_> = t
+            .matches(Ipv4Addr::new(10, 0, 0, 1))
+            .map(|(n, v)| (n, *v))
+            .collect();
+        assert_eq!(matches, vec![(net8, "a"), (net24, "b"), (net32, "c")]);
+    }
+
+    #[test]
+    fn ip_net_trie_dispatches_by_family() {
+        let mut t: IpNetTrie<&str> = IpNetTrie::default();
+        let v4 = IpNet::V4(Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 0), 24).unwrap());
+        let v6 = IpNet::V6(Ipv6Net::new("fd00::".parse().unwrap(), 64).unwrap());
+        t.insert(v4, "v4");
+        t.insert(v6, "v6");
+        assert_eq!(t.len(), 2);
+        assert_eq!(
+            t.longest_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+            Some((v4, &"v4"))
+        );
+        assert_eq!(
+            t.matches(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))).count(),
+            1
+        );
+        assert_eq!(t.remove(v4), Some("v4"));
+        assert_eq!(t.len(), 1);
+    }
+}